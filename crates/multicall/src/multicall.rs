@@ -1,12 +1,12 @@
 //! Multicall
 
-use std::{sync::Arc, time::Duration};
+use std::{any::Any, sync::Arc, time::Duration};
 
 use alloy_contract::{CallBuilder, RawCallBuilder};
 use alloy_network::{Network, TransactionBuilder};
-use alloy_primitives::{Address, Bytes};
-use alloy_provider::Provider;
-use alloy_sol_types::sol;
+use alloy_primitives::{address, Address, Bytes, U256};
+use alloy_provider::{PendingTransactionBuilder, Provider};
+use alloy_sol_types::{sol, SolCall, SolError};
 use alloy_transport::{Transport, TransportErrorKind, TransportResult};
 use parking_lot::RwLock;
 use tokio::{
@@ -23,15 +23,198 @@ sol! {
             bytes callData;
         }
 
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Value {
+            address target;
+            bool allowFailure;
+            uint256 value;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
         /// @notice Backwards-compatible call aggregation with Multicall
         /// @param calls An array of Call structs
         /// @return blockNumber The block number where the calls were executed
         /// @return returnData An array of bytes containing the responses
         function aggregate(Call[] calldata calls) public payable returns (uint256 blockNumber, bytes[] memory returnData);
+
+        /// @notice Aggregate calls, ensuring each returns success if required
+        /// @param calls An array of Call3 structs
+        /// @return returnData An array of Result structs
+        function aggregate3(Call3[] calldata calls) public payable returns (Result[] memory returnData);
+
+        /// @notice Backwards-compatible with Multicall2, allows a global success requirement toggle
+        /// @param requireSuccess If true, require all calls to succeed
+        /// @param calls An array of Call structs
+        /// @return returnData An array of Result structs
+        function tryAggregate(bool requireSuccess, Call[] calldata calls) public payable returns (Result[] memory returnData);
+
+        /// @notice Aggregate calls with a msg value, ensuring each returns success if required
+        /// @param calls An array of Call3Value structs
+        /// @return returnData An array of Result structs
+        function aggregate3Value(Call3Value[] calldata calls) public payable returns (Result[] memory returnData);
+
+        /// @notice Returns the block number
+        function getBlockNumber() public view returns (uint256 blockNumber);
+
+        /// @notice Returns the block timestamp
+        function getCurrentBlockTimestamp() public view returns (uint256 timestamp);
+
+        /// @notice Returns the block gas limit
+        function getCurrentBlockGasLimit() public view returns (uint256 gaslimit);
+
+        /// @notice Returns the block hash for the given block number
+        function getBlockHash(uint256 blockNumber) public view returns (bytes32 blockHash);
+
+        /// @notice Returns the hash of the last block
+        function getLastBlockHash() public view returns (bytes32 blockHash);
+
+        /// @notice Returns the ETH balance of an address
+        function getEthBalance(address addr) public view returns (uint256 balance);
+
+        /// @notice Returns the current block's base fee
+        function getBasefee() public view returns (uint256 basefee);
+    }
+}
+
+use crate::Multicall3::{Call, Call3, Call3Value, Multicall3Instance, Result as Call3Result};
+
+sol! {
+    /// The standard Solidity revert reason, e.g. from `revert("reason")` or a failed `require`.
+    error Error(string reason);
+    /// A Solidity panic, e.g. arithmetic overflow or an out-of-bounds array access.
+    error Panic(uint256 code);
+}
+
+/// Why a single call within a [`Multicall`] batch failed.
+#[derive(Debug, thiserror::Error)]
+pub enum MulticallError {
+    /// The call reverted with a plain `revert("reason")` / failed `require(cond, "reason")`.
+    #[error("call reverted: {0}")]
+    CallReverted(String),
+    /// The call reverted with a Solidity panic.
+    #[error("call panicked with code {0}")]
+    CallPanicked(U256),
+    /// The call reverted with no return data at all, e.g. a bare `revert()` or running out of
+    /// gas.
+    #[error("call reverted with no reason")]
+    CallRevertedNoReason,
+    /// The call reverted with return data that didn't match a known revert encoding, such as a
+    /// custom Solidity error.
+    #[error("call reverted with custom error data: {0}")]
+    CallRevertedCustom(Bytes),
+    /// The call succeeded, but its return data failed to decode as the expected type.
+    #[error("failed to decode return data: {0}")]
+    DecodeError(#[from] alloy_sol_types::Error),
+}
+
+/// Decode a failed call's return data into a [`MulticallError`], recognizing the standard
+/// `Error(string)` and `Panic(uint256)` revert encodings.
+fn decode_revert(data: &Bytes) -> MulticallError {
+    if data.is_empty() {
+        return MulticallError::CallRevertedNoReason;
+    }
+    if let Ok(Error { reason }) = Error::abi_decode(data, true) {
+        return MulticallError::CallReverted(reason);
+    }
+    if let Ok(Panic { code }) = Panic::abi_decode(data, true) {
+        return MulticallError::CallPanicked(code);
+    }
+    MulticallError::CallRevertedCustom(data.clone())
+}
+
+/// A boxed decoder for a single call's return data, erased to [`Any`] so that calls with
+/// different return types can share one batch.
+type DecodeFn =
+    Box<dyn Fn(Bytes) -> Result<Box<dyn Any + Send + Sync>, MulticallError> + Send + Sync>;
+
+/// The decoded result of each call in a [`Multicall`] batch, in the order the calls were added.
+///
+/// Each element is `Ok(value)` on success, where `value` can be recovered with
+/// [`Any::downcast_ref`]/[`Any::downcast`] to the [`SolCall::Return`] type of the call that
+/// produced it, i.e. the generated `<function>Return` struct rather than its bare fields (or to
+/// [`Bytes`] for calls added with [`Multicall::add_call_raw`]/[`Multicall::add_call_raw_failable`],
+/// which have no decoder), or `Err(reason)` if the call reverted.
+pub type CallResults = Vec<Result<Box<dyn Any + Send + Sync>, MulticallError>>;
+
+/// A single call staged in a [`Multicall`] batch.
+struct CallItem<T, P, N> {
+    call: RawCallBuilder<T, P, N>,
+    allow_failure: bool,
+    value: U256,
+    /// `None` for calls added via [`Multicall::add_call_raw`]/[`Multicall::add_call_raw_failable`],
+    /// which carry no type information to decode their return data with.
+    decode: Option<DecodeFn>,
+}
+
+/// Decode a single call's return data using its captured decoder, or hand back the raw
+/// [`Bytes`] if the call was added without one (i.e. via a `*_raw` add method).
+fn decode_return(
+    decode: &Option<DecodeFn>,
+    data: Bytes,
+) -> Result<Box<dyn Any + Send + Sync>, MulticallError> {
+    match decode {
+        Some(decode) => decode(data),
+        None => Ok(Box::new(data) as Box<dyn Any + Send + Sync>),
     }
 }
 
-use crate::Multicall3::{aggregateReturn as AggregateReturn, Call, Multicall3Instance};
+/// The Multicall contract version to target.
+///
+/// The three versions mirror the deployed Multicall contracts and the entry points they expose:
+/// - [`MulticallVersion::Multicall`]: only `aggregate`, which reverts the whole batch if any call
+///   fails.
+/// - [`MulticallVersion::Multicall2`]: adds `tryAggregate`, which can relax that to a single
+///   batch-wide success requirement.
+/// - [`MulticallVersion::Multicall3`]: adds `aggregate3`/`aggregate3Value`, which allow failure
+///   and `value` to be set per call.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MulticallVersion {
+    /// Only `aggregate(Call[])` is available. This is the default, matching the behavior of
+    /// [`Multicall::call`] prior to the introduction of this enum.
+    #[default]
+    Multicall,
+    /// `aggregate` and `tryAggregate(bool, Call[])` are available.
+    Multicall2,
+    /// The full `aggregate3`/`aggregate3Value` interface is available.
+    Multicall3,
+}
+
+/// The canonical address at which Multicall3 is deployed, via a deterministic deployment proxy,
+/// on every chain in [`MULTICALL3_SUPPORTED_CHAINS`].
+///
+/// See <https://github.com/mds1/multicall> for details.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Chain IDs where [`MULTICALL3_ADDRESS`] is known to hold the Multicall3 deployment.
+///
+/// This list is not exhaustive of every chain Multicall3 has been deployed to, but covers the
+/// common ones; see <https://github.com/mds1/multicall#deployments> for the full list.
+pub const MULTICALL3_SUPPORTED_CHAINS: &[u64] = &[
+    1,        // Ethereum Mainnet
+    5,        // Goerli
+    10,       // Optimism
+    11155111, // Sepolia
+    25,       // Cronos
+    56,       // BNB Smart Chain
+    100,      // Gnosis Chain
+    137,      // Polygon
+    250,      // Fantom
+    1284,     // Moonbeam
+    8453,     // Base
+    42161,    // Arbitrum One
+    42220,    // Celo
+    43114,    // Avalanche
+];
 
 /// Multicall
 pub struct Multicall<T, P, N>
@@ -44,8 +227,10 @@ where
     interval: Duration,
     /// Multicall3 Instance
     instance: Multicall3Instance<T, P, N>,
-    /// Calls to be made
-    calls: Arc<RwLock<Vec<RawCallBuilder<T, P, N>>>>,
+    /// The contract version to dispatch calls to.
+    version: MulticallVersion,
+    /// Calls to be made.
+    calls: Arc<RwLock<Vec<CallItem<T, P, N>>>>,
 }
 
 impl<T, P, N> Multicall<T, P, N>
@@ -59,61 +244,380 @@ where
         Self {
             interval: Duration::from_millis(50),
             instance: Multicall3::new(address, provider),
+            version: MulticallVersion::default(),
             calls: Default::default(),
         }
     }
 
+    /// Create a new Multicall instance at the canonical [`MULTICALL3_ADDRESS`], after checking
+    /// that the connected chain is one where that deployment is known to exist.
+    ///
+    /// This avoids the footgun of hardcoding the wrong Multicall3 address for a chain and getting
+    /// back silent empty results. Returns an error if the provider's chain ID is not in
+    /// [`MULTICALL3_SUPPORTED_CHAINS`].
+    pub async fn new_on_chain(provider: P) -> TransportResult<Self> {
+        let chain_id = provider.get_chain_id().await.map_err(TransportErrorKind::custom)?;
+
+        if !MULTICALL3_SUPPORTED_CHAINS.contains(&chain_id) {
+            return Err(TransportErrorKind::custom_str(&format!(
+                "Multicall3 is not known to be deployed at {MULTICALL3_ADDRESS} on chain {chain_id}"
+            )));
+        }
+
+        Ok(Self::new(MULTICALL3_ADDRESS, provider))
+    }
+
     /// Set the interval (milliseconds) at which calls are drained
     pub fn with_interval(mut self, interval: u64) -> Self {
         self.interval = Duration::from_millis(interval);
         self
     }
 
-    /// Add a call to the Multicall
-    pub fn add_call<D>(&self, call: &CallBuilder<T, &P, D, N>) {
+    /// Set the Multicall contract version to dispatch calls to. Defaults to
+    /// [`MulticallVersion::Multicall`].
+    ///
+    /// This is only relevant to the deployed contract at the configured address: targeting an
+    /// older deployment that doesn't expose `aggregate3`/`aggregate3Value` requires selecting
+    /// [`MulticallVersion::Multicall`] or [`MulticallVersion::Multicall2`] here.
+    pub fn with_version(mut self, version: MulticallVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Add a call to the Multicall. If the call reverts, the whole batch reverts.
+    pub fn add_call<D>(&self, call: &CallBuilder<T, &P, D, N>)
+    where
+        D: SolCall,
+        D::Return: Send + Sync + 'static,
+    {
+        self.add_call_inner(call, false, U256::ZERO);
+    }
+
+    /// Add a call to the Multicall that is allowed to revert. Requires the batch to be executed
+    /// with [`Multicall::call_allow_failure`] (or a version that supports `aggregate3`) so that a
+    /// revert in this call doesn't fail the other calls in the batch.
+    pub fn add_call_failable<D>(&self, call: &CallBuilder<T, &P, D, N>)
+    where
+        D: SolCall,
+        D::Return: Send + Sync + 'static,
+    {
+        self.add_call_inner(call, true, U256::ZERO);
+    }
+
+    /// Add a payable call to the Multicall, preserving the `value` set on the underlying
+    /// [`CallBuilder`]'s transaction request.
+    ///
+    /// Batches containing value-bearing calls must be executed with [`Multicall::send_value`],
+    /// since sending ETH requires a transaction rather than an `eth_call`. Requires
+    /// [`MulticallVersion::Multicall3`], since `aggregate3Value` is not available on older
+    /// deployments.
+    pub fn add_call_value<D>(&self, call: &CallBuilder<T, &P, D, N>)
+    where
+        D: SolCall,
+        D::Return: Send + Sync + 'static,
+    {
+        let value = call.as_ref().value().unwrap_or_default();
+        self.add_call_inner(call, false, value);
+    }
+
+    /// Add a raw, untyped call to the Multicall. Since there's no [`SolCall`] to decode the
+    /// return data with, its result is reported as raw [`Bytes`].
+    ///
+    /// Useful for probing a method that may not exist on the target contract, where no ABI is
+    /// available to build a typed call with in the first place.
+    pub fn add_call_raw(&self, call: &RawCallBuilder<T, &P, N>) {
+        self.add_raw_call_inner(call, false, U256::ZERO);
+    }
+
+    /// Add a raw, untyped call to the Multicall that is allowed to revert. Requires the batch to
+    /// be executed with [`Multicall::call_allow_failure`] (or a version that supports
+    /// `aggregate3`) so that a revert in this call doesn't fail the other calls in the batch.
+    ///
+    /// This is the untyped counterpart to [`Multicall::add_call_failable`], for the common case
+    /// of probing a method that may not exist on the target contract: since there's no ABI to
+    /// decode a raw call's return data with, its result is reported as raw [`Bytes`].
+    pub fn add_call_raw_failable(&self, call: &RawCallBuilder<T, &P, N>) {
+        self.add_raw_call_inner(call, true, U256::ZERO);
+    }
+
+    /// Build the [`RawCallBuilder`] staged for a [`Multicall`] batch from a typed or raw call,
+    /// discarding everything but its `to` and `input`.
+    fn to_raw_call<D>(call: &CallBuilder<T, &P, D, N>) -> RawCallBuilder<T, P, N> {
         let req = call.as_ref();
 
-        let raw = RawCallBuilder::new_raw(
+        RawCallBuilder::new_raw(
             call.provider,
             req.input().map_or(Bytes::new(), |input| input.clone()),
         )
         .to(req.to().unwrap_or_default())
-        .with_cloned_provider();
+        .with_cloned_provider()
+    }
+
+    fn add_call_inner<D>(&self, call: &CallBuilder<T, &P, D, N>, allow_failure: bool, value: U256)
+    where
+        D: SolCall,
+        D::Return: Send + Sync + 'static,
+    {
+        let raw = Self::to_raw_call(call);
+
+        let decode: DecodeFn = Box::new(|data: Bytes| {
+            D::abi_decode_returns(&data, true)
+                .map(|decoded| Box::new(decoded) as Box<dyn Any + Send + Sync>)
+                .map_err(MulticallError::DecodeError)
+        });
+
+        self.calls.write().push(CallItem { call: raw, allow_failure, value, decode: Some(decode) });
+    }
+
+    fn add_raw_call_inner(
+        &self,
+        call: &RawCallBuilder<T, &P, N>,
+        allow_failure: bool,
+        value: U256,
+    ) {
+        let raw = Self::to_raw_call(call);
 
-        let mut calls = self.calls.write();
+        self.calls.write().push(CallItem { call: raw, allow_failure, value, decode: None });
+    }
 
-        calls.push(raw);
+    /// Add Multicall3's `getBlockNumber()` to the batch, so the block number the batch executed
+    /// at is returned alongside the rest of the calls, avoiding a race between separate RPCs.
+    pub fn add_get_block_number(&self) {
+        self.add_call_inner(&self.instance.getBlockNumber(), false, U256::ZERO);
     }
 
-    /// Execute the calls
+    /// Add Multicall3's `getCurrentBlockTimestamp()` to the batch.
+    pub fn add_get_block_timestamp(&self) {
+        self.add_call_inner(&self.instance.getCurrentBlockTimestamp(), false, U256::ZERO);
+    }
+
+    /// Add Multicall3's `getCurrentBlockGasLimit()` to the batch.
+    pub fn add_get_block_gas_limit(&self) {
+        self.add_call_inner(&self.instance.getCurrentBlockGasLimit(), false, U256::ZERO);
+    }
+
+    /// Add Multicall3's `getBlockHash(uint256)` to the batch, returning the hash of `block_number`.
+    pub fn add_get_block_hash(&self, block_number: U256) {
+        self.add_call_inner(&self.instance.getBlockHash(block_number), false, U256::ZERO);
+    }
+
+    /// Add Multicall3's `getLastBlockHash()` to the batch, returning the parent block's hash.
+    pub fn add_get_last_block_hash(&self) {
+        self.add_call_inner(&self.instance.getLastBlockHash(), false, U256::ZERO);
+    }
+
+    /// Add Multicall3's `getEthBalance(address)` to the batch, returning `address`'s ETH balance
+    /// at the block the batch executed at.
+    pub fn add_get_eth_balance(&self, address: Address) {
+        self.add_call_inner(&self.instance.getEthBalance(address), false, U256::ZERO);
+    }
+
+    /// Add Multicall3's `getBasefee()` to the batch.
+    pub fn add_get_basefee(&self) {
+        self.add_call_inner(&self.instance.getBasefee(), false, U256::ZERO);
+    }
+
+    /// Decode the `Call3Result`s returned by `tryAggregate`/`aggregate3`, bubbling up each failed
+    /// call's revert reason via [`decode_revert`].
+    fn decode_results(items: &[CallItem<T, P, N>], results: Vec<Call3Result>) -> CallResults {
+        items
+            .iter()
+            .zip(results)
+            .map(|(item, Call3Result { success, returnData })| {
+                if success {
+                    decode_return(&item.decode, returnData)
+                } else {
+                    Err(decode_revert(&returnData))
+                }
+            })
+            .collect()
+    }
+
+    /// Execute the calls, dispatching to the contract function appropriate for the configured
+    /// [`MulticallVersion`], and decode each call's return data using the decoder captured when
+    /// it was added.
     ///
-    /// Note:
+    /// The calls are executed in the order they are added, and the returned [`CallResults`]
+    /// preserves that order.
     ///
-    /// The calls are executed in the order they are added.
-    pub async fn call(self) -> TransportResult<AggregateReturn> {
-        let builders = self.calls.write().drain(..).collect::<Vec<_>>();
+    /// Returns an error if any call added with [`Multicall::add_call_failable`] is incompatible
+    /// with [`MulticallVersion::Multicall`], which has no notion of per-call failure tolerance.
+    pub async fn call(self) -> TransportResult<CallResults> {
+        let version = self.version;
+        let items = self.calls.write().drain(..).collect::<Vec<_>>();
+
+        if version == MulticallVersion::Multicall && items.iter().any(|item| item.allow_failure) {
+            return Err(TransportErrorKind::custom_str(
+                "MulticallVersion::Multicall does not support per-call failure tolerance; use \
+                 Multicall2 or Multicall3",
+            ));
+        }
+
+        if version == MulticallVersion::Multicall2
+            && items.iter().any(|item| item.allow_failure)
+            && !items.iter().all(|item| item.allow_failure)
+        {
+            return Err(TransportErrorKind::custom_str(
+                "MulticallVersion::Multicall2 cannot mix failable and non-failable calls in \
+                 the same batch; `tryAggregate` only exposes a single batch-wide \
+                 `requireSuccess` toggle, so allowing one call to fail would also let \
+                 non-failable calls silently revert. Use Multicall3 for per-call failure \
+                 tolerance.",
+            ));
+        }
+
+        if items.iter().any(|item| item.value > U256::ZERO) {
+            return Err(TransportErrorKind::custom_str(
+                "value-bearing calls added with `add_call_value` must be executed with \
+                 `Multicall::send_value`, not `call`",
+            ));
+        }
+
+        let mut targets = Vec::new();
+        for item in &items {
+            let tx = item.call.as_ref().clone();
+            if tx.to().is_none() || tx.kind().is_some_and(|k| k.is_create()) {
+                return Err(TransportErrorKind::custom_str("invalid `to` address"));
+            }
+
+            let input = tx.input().map_or(Bytes::new(), |input| input.clone());
+            targets.push((tx.to().unwrap(), input));
+        }
+
+        match version {
+            MulticallVersion::Multicall => {
+                let calls = targets
+                    .iter()
+                    .map(|(target, call_data)| Call {
+                        target: *target,
+                        callData: call_data.clone(),
+                    })
+                    .collect();
+
+                let result = self
+                    .instance
+                    .aggregate(calls)
+                    .call()
+                    .await
+                    .map_err(TransportErrorKind::custom)?;
+
+                Ok(items
+                    .iter()
+                    .zip(result.returnData)
+                    .map(|(item, data)| decode_return(&item.decode, data))
+                    .collect())
+            }
+            MulticallVersion::Multicall2 => {
+                let calls = targets
+                    .iter()
+                    .map(|(target, call_data)| Call {
+                        target: *target,
+                        callData: call_data.clone(),
+                    })
+                    .collect();
+
+                // Safe to collapse to a single batch-wide toggle: the guard above already
+                // rejected batches that mix failable and non-failable calls.
+                let require_success = !items.iter().any(|item| item.allow_failure);
+                let results = self
+                    .instance
+                    .tryAggregate(require_success, calls)
+                    .call()
+                    .await
+                    .map_err(TransportErrorKind::custom)?;
+
+                Ok(Self::decode_results(&items, results.returnData))
+            }
+            MulticallVersion::Multicall3 => {
+                let calls = items
+                    .iter()
+                    .zip(&targets)
+                    .map(|(item, (target, call_data))| Call3 {
+                        target: *target,
+                        allowFailure: item.allow_failure,
+                        callData: call_data.clone(),
+                    })
+                    .collect();
+
+                let results = self
+                    .instance
+                    .aggregate3(calls)
+                    .call()
+                    .await
+                    .map_err(TransportErrorKind::custom)?;
+
+                Ok(Self::decode_results(&items, results.returnData))
+            }
+        }
+    }
+
+    /// Execute the calls via Multicall3's `aggregate3`, allowing calls added with
+    /// [`Multicall::add_call_failable`] to revert without failing the rest of the batch, and
+    /// decoding each call's return data the same way [`Multicall::call`] does.
+    ///
+    /// This forces [`MulticallVersion::Multicall3`] dispatch regardless of the version previously
+    /// selected via [`Multicall::with_version`], since `aggregate3` is the only entry point with
+    /// per-call failure tolerance; it exists as an explicit entry point for that common case
+    /// instead of requiring `with_version(MulticallVersion::Multicall3).call()`.
+    pub async fn call_allow_failure(mut self) -> TransportResult<CallResults> {
+        self.version = MulticallVersion::Multicall3;
+        self.call().await
+    }
+
+    /// Send the calls as a single transaction via Multicall3's `aggregate3Value`, summing the
+    /// `value` of each call added with [`Multicall::add_call_value`] to set the outer
+    /// transaction's `value`.
+    ///
+    /// Unlike [`Multicall::call`] and [`Multicall::call_allow_failure`], this sends a transaction
+    /// rather than performing an `eth_call`, since a batch that transfers ETH cannot be simulated
+    /// as a read. Returns a pending transaction handle rather than decoded return data.
+    ///
+    /// Requires [`MulticallVersion::Multicall3`], since `aggregate3Value` is not available on
+    /// older deployments.
+    pub async fn send_value(self) -> TransportResult<PendingTransactionBuilder<T, N>> {
+        if self.version != MulticallVersion::Multicall3 {
+            return Err(TransportErrorKind::custom_str(
+                "value-bearing batches require MulticallVersion::Multicall3",
+            ));
+        }
+
+        let items = self.calls.write().drain(..).collect::<Vec<_>>();
         let mut calls = Vec::new();
-        for call in builders {
-            let tx = call.as_ref().clone();
+        let mut total_value = U256::ZERO;
+        for item in items {
+            let tx = item.call.as_ref().clone();
             if tx.to().is_none() || tx.kind().is_some_and(|k| k.is_create()) {
                 return Err(TransportErrorKind::custom_str("invalid `to` address"));
             }
 
-            calls.push(Call {
+            total_value += item.value;
+            calls.push(Call3Value {
                 target: tx.to().unwrap(),
+                allowFailure: item.allow_failure,
+                value: item.value,
                 callData: tx.input().map_or(Bytes::new(), |input| input.clone()),
             });
         }
 
-        self.instance.aggregate(calls).call().await.map_err(TransportErrorKind::custom)
+        self.instance
+            .aggregate3Value(calls)
+            .value(total_value)
+            .send()
+            .await
+            .map_err(TransportErrorKind::custom)
     }
 
-    /// Spawn a task to execute the calls every `interval` milliseconds
+    /// Spawn a task to execute the calls every `interval` milliseconds, decoding each call's
+    /// return data the same way [`Multicall::call`] does.
+    ///
+    /// Like [`Multicall::call`], this polls via `eth_call` and so cannot carry a `value`; a batch
+    /// containing a call added with [`Multicall::add_call_value`] fails the task with an error
+    /// instead of silently dropping that call's `value`.
     pub fn spawn_task(
         self,
-    ) -> (JoinHandle<TransportResult<()>>, UnboundedReceiver<TransportResult<AggregateReturn>>)
-    {
+    ) -> (JoinHandle<TransportResult<()>>, UnboundedReceiver<TransportResult<CallResults>>) {
         let instance = self.instance.clone();
+        let version = self.version;
         let calls = self.calls.clone();
         let mut interval = time::interval(self.interval);
         let (tx, rx) = mpsc::unbounded_channel();
@@ -126,28 +630,111 @@ where
                     break;
                 }
 
-                let builders = calls.write().drain(..).collect::<Vec<_>>();
-                if builders.is_empty() {
+                let items = calls.write().drain(..).collect::<Vec<_>>();
+                if items.is_empty() {
                     continue;
                 }
 
-                let mut multicall_calls = Vec::new();
-                for call in builders {
-                    let tx = call.as_ref().clone();
+                if version == MulticallVersion::Multicall
+                    && items.iter().any(|item| item.allow_failure)
+                {
+                    return Err(TransportErrorKind::custom_str(
+                        "MulticallVersion::Multicall does not support per-call failure \
+                         tolerance; use Multicall2 or Multicall3",
+                    ));
+                }
+
+                if version == MulticallVersion::Multicall2
+                    && items.iter().any(|item| item.allow_failure)
+                    && !items.iter().all(|item| item.allow_failure)
+                {
+                    return Err(TransportErrorKind::custom_str(
+                        "MulticallVersion::Multicall2 cannot mix failable and non-failable \
+                         calls in the same batch; `tryAggregate` only exposes a single \
+                         batch-wide `requireSuccess` toggle, so allowing one call to fail would \
+                         also let non-failable calls silently revert. Use Multicall3 for \
+                         per-call failure tolerance.",
+                    ));
+                }
+
+                if items.iter().any(|item| item.value > U256::ZERO) {
+                    return Err(TransportErrorKind::custom_str(
+                        "value-bearing calls added with `add_call_value` must be executed with \
+                         `Multicall::send_value`, not polled via `spawn_task`",
+                    ));
+                }
+
+                let mut targets = Vec::new();
+                for item in &items {
+                    let tx = item.call.as_ref().clone();
                     if tx.to().is_none() || tx.kind().is_some_and(|k| k.is_create()) {
                         return Err(TransportErrorKind::custom_str("invalid `to` address"));
                     }
 
-                    multicall_calls.push(Call {
-                        target: tx.to().unwrap(),
-                        callData: tx.input().map_or(Bytes::new(), |input| input.clone()),
-                    });
+                    let input = tx.input().map_or(Bytes::new(), |input| input.clone());
+                    targets.push((tx.to().unwrap(), input));
                 }
 
-                let aggregate = instance.aggregate(multicall_calls);
-                let result = match aggregate.call().await {
-                    Ok(result) => Ok(result),
-                    Err(e) => Err(TransportErrorKind::custom(e)),
+                let result = match version {
+                    MulticallVersion::Multicall => {
+                        let multicall_calls = targets
+                            .iter()
+                            .map(|(target, call_data)| Call {
+                                target: *target,
+                                callData: call_data.clone(),
+                            })
+                            .collect();
+
+                        instance
+                            .aggregate(multicall_calls)
+                            .call()
+                            .await
+                            .map_err(TransportErrorKind::custom)
+                            .map(|result| {
+                                items
+                                    .iter()
+                                    .zip(result.returnData)
+                                    .map(|(item, data)| decode_return(&item.decode, data))
+                                    .collect()
+                            })
+                    }
+                    MulticallVersion::Multicall2 => {
+                        let multicall_calls = targets
+                            .iter()
+                            .map(|(target, call_data)| Call {
+                                target: *target,
+                                callData: call_data.clone(),
+                            })
+                            .collect();
+
+                        // Safe to collapse to a single batch-wide toggle: the guard above
+                        // already rejected batches that mix failable and non-failable calls.
+                        let require_success = !items.iter().any(|item| item.allow_failure);
+                        instance
+                            .tryAggregate(require_success, multicall_calls)
+                            .call()
+                            .await
+                            .map_err(TransportErrorKind::custom)
+                            .map(|results| Self::decode_results(&items, results.returnData))
+                    }
+                    MulticallVersion::Multicall3 => {
+                        let multicall_calls = items
+                            .iter()
+                            .zip(&targets)
+                            .map(|(item, (target, call_data))| Call3 {
+                                target: *target,
+                                allowFailure: item.allow_failure,
+                                callData: call_data.clone(),
+                            })
+                            .collect();
+
+                        instance
+                            .aggregate3(multicall_calls)
+                            .call()
+                            .await
+                            .map_err(TransportErrorKind::custom)
+                            .map(|results| Self::decode_results(&items, results.returnData))
+                    }
                 };
 
                 if tx.send(result).is_err() {
@@ -166,7 +753,6 @@ where
 mod test {
     use super::*;
     use alloy_node_bindings::Anvil;
-    use alloy_primitives::address;
     use alloy_provider::ProviderBuilder;
 
     sol! {
@@ -203,36 +789,22 @@ mod test {
         multicall.add_call(&symbol);
         multicall.add_call(&decimals);
 
-        let result = multicall.call().await.unwrap();
+        let results = multicall.call().await.unwrap();
 
-        let block_number = result.blockNumber;
+        let total_supply =
+            &results[0].as_ref().unwrap().downcast_ref::<IERC20::totalSupplyReturn>().unwrap()._0;
+        println!("Total Supply: {total_supply:?}");
 
-        assert_eq!(block_number.to::<u64>(), fork_block_number);
-        let return_data = result.returnData;
-
-        // ABI decode the return data
-        for (i, return_data) in return_data.into_iter().enumerate() {
-            match i {
-                0 => {
-                    let total_supply =
-                        total_supply.decode_output(return_data.clone(), true).unwrap();
-                    println!("Total Supply: {:?}", total_supply);
-                }
-                1 => {
-                    let name = name.decode_output(return_data.clone(), true).unwrap();
-                    println!("Name: {:?}", name);
-                }
-                2 => {
-                    let symbol = symbol.decode_output(return_data.clone(), true).unwrap();
-                    println!("Symbol: {:?}", symbol);
-                }
-                3 => {
-                    let decimals = decimals.decode_output(return_data.clone(), true).unwrap();
-                    println!("Decimals: {:?}", decimals);
-                }
-                _ => {}
-            }
-        }
+        let name = &results[1].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+        assert_eq!(name, "Wrapped Ether");
+
+        let symbol =
+            &results[2].as_ref().unwrap().downcast_ref::<IERC20::symbolReturn>().unwrap()._0;
+        assert_eq!(symbol, "WETH");
+
+        let decimals =
+            &results[3].as_ref().unwrap().downcast_ref::<IERC20::decimalsReturn>().unwrap()._0;
+        assert_eq!(*decimals, 18);
     }
 
     #[tokio::test]
@@ -263,35 +835,30 @@ mod test {
         let recv = rx.recv().await;
 
         match recv {
-            Some(Ok(result)) => {
-                let block_number = result.blockNumber;
-                assert_eq!(block_number.to::<u64>(), fork_block_number);
-                let return_data = result.returnData;
-
-                // ABI decode the return data
-                for (i, return_data) in return_data.into_iter().enumerate() {
-                    match i {
-                        0 => {
-                            let total_supply =
-                                total_supply.decode_output(return_data.clone(), true).unwrap();
-                            println!("Total Supply: {:?}", total_supply);
-                        }
-                        1 => {
-                            let name = name.decode_output(return_data.clone(), true).unwrap();
-                            println!("Name: {:?}", name);
-                        }
-                        2 => {
-                            let symbol = symbol.decode_output(return_data.clone(), true).unwrap();
-                            println!("Symbol: {:?}", symbol);
-                        }
-                        3 => {
-                            let decimals =
-                                decimals.decode_output(return_data.clone(), true).unwrap();
-                            println!("Decimals: {:?}", decimals);
-                        }
-                        _ => {}
-                    }
-                }
+            Some(Ok(results)) => {
+                let total_supply = &results[0]
+                    .as_ref()
+                    .unwrap()
+                    .downcast_ref::<IERC20::totalSupplyReturn>()
+                    .unwrap()
+                    ._0;
+                println!("Total Supply: {total_supply:?}");
+
+                let name =
+                    &results[1].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+                assert_eq!(name, "Wrapped Ether");
+
+                let symbol =
+                    &results[2].as_ref().unwrap().downcast_ref::<IERC20::symbolReturn>().unwrap()._0;
+                assert_eq!(symbol, "WETH");
+
+                let decimals = &results[3]
+                    .as_ref()
+                    .unwrap()
+                    .downcast_ref::<IERC20::decimalsReturn>()
+                    .unwrap()
+                    ._0;
+                assert_eq!(*decimals, 18);
             }
             Some(Err(e)) => {
                 println!("Error: {:?}", e);
@@ -304,4 +871,161 @@ mod test {
         drop(rx);
         let _ = handle.await.unwrap().unwrap();
     }
+
+    #[tokio::test]
+    async fn test_multicall_call_allow_failure() {
+        let fork_url = "https://eth-mainnet.alchemyapi.io/v2/jGiK5vwDfC3F4r0bqukm-W2GqgdrxdSr";
+        let fork_block_number = 21112416;
+        let anvil = Anvil::new().fork(fork_url).fork_block_number(fork_block_number).spawn();
+        let multicall_address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        // `call_allow_failure` forces `aggregate3` dispatch itself, so the default
+        // `MulticallVersion::Multicall` is left unchanged here on purpose.
+        let multicall = Multicall::new(multicall_address, provider.clone());
+
+        let weth_addr = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let weth = IERC20::new(weth_addr, provider.clone());
+
+        // `name()` succeeds, but this selector does not exist on the Multicall3 contract, which
+        // has no fallback, so it should revert without failing the whole batch. (WETH9 would be
+        // the wrong target here: its `payable` fallback routes any unknown selector to
+        // `deposit()`, which succeeds rather than reverting.)
+        let name = weth.name();
+        let missing = RawCallBuilder::new_raw(&provider, Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]))
+            .to(multicall_address);
+
+        multicall.add_call(&name);
+        multicall.add_call_raw_failable(&missing);
+
+        let results = multicall.call_allow_failure().await.unwrap();
+
+        let name = &results[0].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+        assert_eq!(name, "Wrapped Ether");
+
+        assert!(results[1].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_multicall_call_version_multicall2() {
+        let fork_url = "https://eth-mainnet.alchemyapi.io/v2/jGiK5vwDfC3F4r0bqukm-W2GqgdrxdSr";
+        let fork_block_number = 21112416;
+        let anvil = Anvil::new().fork(fork_url).fork_block_number(fork_block_number).spawn();
+        let multicall_address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let multicall = Multicall::new(multicall_address, provider.clone())
+            .with_version(MulticallVersion::Multicall2);
+
+        let weth_addr = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let weth = IERC20::new(weth_addr, provider.clone());
+
+        let name = weth.name();
+        multicall.add_call(&name);
+
+        let results = multicall.call().await.unwrap();
+        let name = &results[0].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+        assert_eq!(name, "Wrapped Ether");
+    }
+
+    sol! {
+        #[sol(rpc)]
+        #[derive(Debug)]
+        contract IWETH {
+            function deposit() external payable;
+            function balanceOf(address account) external view returns (uint256);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multicall_send_value() {
+        let fork_url = "https://eth-mainnet.alchemyapi.io/v2/jGiK5vwDfC3F4r0bqukm-W2GqgdrxdSr";
+        let fork_block_number = 21112416;
+        let anvil = Anvil::new().fork(fork_url).fork_block_number(fork_block_number).spawn();
+        let multicall_address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let multicall = Multicall::new(multicall_address, provider.clone())
+            .with_version(MulticallVersion::Multicall3);
+
+        let weth_addr = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let weth = IWETH::new(weth_addr, provider.clone());
+
+        let deposit = weth.deposit().value(alloy_primitives::U256::from(1_000_000_000_000_000_u64));
+        multicall.add_call_value(&deposit);
+
+        let pending = multicall.send_value().await.unwrap();
+        let receipt = pending.get_receipt().await.unwrap();
+        assert!(receipt.status());
+    }
+
+    #[tokio::test]
+    async fn test_multicall_new_on_chain() {
+        let fork_url = "https://eth-mainnet.alchemyapi.io/v2/jGiK5vwDfC3F4r0bqukm-W2GqgdrxdSr";
+        let fork_block_number = 21112416;
+        let anvil = Anvil::new().fork(fork_url).fork_block_number(fork_block_number).spawn();
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let multicall = Multicall::new_on_chain(provider.clone()).await.unwrap();
+
+        let weth_addr = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let weth = IERC20::new(weth_addr, provider.clone());
+
+        let name = weth.name();
+        multicall.add_call(&name);
+
+        let results = multicall.call().await.unwrap();
+        let name = &results[0].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+        assert_eq!(name, "Wrapped Ether");
+    }
+
+    #[tokio::test]
+    async fn test_multicall_new_on_chain_unsupported() {
+        // Anvil's default dev chain ID (31337) is not in `MULTICALL3_SUPPORTED_CHAINS`.
+        let anvil = Anvil::new().spawn();
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let result = Multicall::new_on_chain(provider).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_multicall_block_helpers() {
+        let fork_url = "https://eth-mainnet.alchemyapi.io/v2/jGiK5vwDfC3F4r0bqukm-W2GqgdrxdSr";
+        let fork_block_number = 21112416;
+        let anvil = Anvil::new().fork(fork_url).fork_block_number(fork_block_number).spawn();
+        let multicall_address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        let multicall = Multicall::new(multicall_address, provider.clone());
+
+        let weth_addr = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let weth = IERC20::new(weth_addr, provider.clone());
+        let name = weth.name();
+
+        multicall.add_get_block_number();
+        multicall.add_get_eth_balance(weth_addr);
+        multicall.add_call(&name);
+
+        let results = multicall.call().await.unwrap();
+
+        let block_number = results[0]
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<Multicall3::getBlockNumberReturn>()
+            .unwrap()
+            .blockNumber;
+        assert_eq!(block_number.to::<u64>(), fork_block_number);
+
+        let balance = results[1]
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<Multicall3::getEthBalanceReturn>()
+            .unwrap()
+            .balance;
+        println!("WETH contract balance: {balance:?}");
+
+        let name = &results[2].as_ref().unwrap().downcast_ref::<IERC20::nameReturn>().unwrap()._0;
+        assert_eq!(name, "Wrapped Ether");
+    }
 }